@@ -0,0 +1,273 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// The single source of truth for the instruction set: every opcode's
+// mnemonic, its 4-bit opcode, the bit that distinguishes it from a sibling
+// variant sharing the same opcode (if any), any bits that are always fixed
+// on encode, and its operand fields. `ops::decode`, `ops::encode`, the `Op`
+// enum, and `ops::disassemble` are all generated from this table so they
+// can never drift out of sync with each other.
+#[derive(Clone, Copy)]
+enum FieldKind {
+    Reg,
+    Offset,
+    Byte,
+    // A single condition-code bit (Branch's n/z/p) rendered as a suffix
+    // letter on the mnemonic instead of as a normal operand.
+    Flag(char),
+}
+
+struct Field {
+    name: &'static str,
+    start: i16,
+    end: i16,
+    kind: FieldKind,
+}
+
+struct Fixed {
+    start: i16,
+    end: i16,
+    value: u16,
+}
+
+struct Variant {
+    name: &'static str,
+    mnemonic: &'static str,
+    opcode: u16,
+    discriminator: Option<(i16, u16)>,
+    fixed: Vec<Fixed>,
+    fields: Vec<Field>,
+}
+
+fn reg(name: &'static str, start: i16, end: i16) -> Field {
+    return Field { name, start, end, kind: FieldKind::Reg };
+}
+
+fn offset(name: &'static str, start: i16, end: i16) -> Field {
+    return Field { name, start, end, kind: FieldKind::Offset };
+}
+
+fn byte(name: &'static str, start: i16, end: i16) -> Field {
+    return Field { name, start, end, kind: FieldKind::Byte };
+}
+
+fn flag(name: &'static str, bit: i16, letter: char) -> Field {
+    return Field { name, start: bit, end: bit, kind: FieldKind::Flag(letter) };
+}
+
+fn fixed(start: i16, end: i16, value: u16) -> Fixed {
+    return Fixed { start, end, value };
+}
+
+fn instructions() -> Vec<Variant> {
+    return vec![
+        Variant { name: "Branch", mnemonic: "BR", opcode: 0b0000, discriminator: None, fixed: vec![],
+            fields: vec![flag("n", 11, 'n'), flag("z", 10, 'z'), flag("p", 9, 'p'), offset("offset", 8, 0)] },
+        Variant { name: "AddReg", mnemonic: "ADD", opcode: 0b0001, discriminator: Some((5, 0)), fixed: vec![],
+            fields: vec![reg("dst", 11, 9), reg("src1", 8, 6), reg("src2", 2, 0)] },
+        Variant { name: "AddImm", mnemonic: "ADD", opcode: 0b0001, discriminator: Some((5, 1)), fixed: vec![],
+            fields: vec![reg("dst", 11, 9), reg("src", 8, 6), offset("imm", 4, 0)] },
+        Variant { name: "Load", mnemonic: "LD", opcode: 0b0010, discriminator: None, fixed: vec![],
+            fields: vec![reg("dst", 11, 9), offset("offset", 8, 0)] },
+        Variant { name: "Store", mnemonic: "ST", opcode: 0b0011, discriminator: None, fixed: vec![],
+            fields: vec![reg("src", 11, 9), offset("offset", 8, 0)] },
+        Variant { name: "CallReg", mnemonic: "JSRR", opcode: 0b0100, discriminator: Some((11, 0)), fixed: vec![],
+            fields: vec![reg("src", 8, 6)] },
+        Variant { name: "Call", mnemonic: "JSR", opcode: 0b0100, discriminator: Some((11, 1)), fixed: vec![],
+            fields: vec![offset("offset", 10, 0)] },
+        Variant { name: "AndReg", mnemonic: "AND", opcode: 0b0101, discriminator: Some((5, 0)), fixed: vec![],
+            fields: vec![reg("dst", 11, 9), reg("src1", 8, 6), reg("src2", 2, 0)] },
+        Variant { name: "AndImm", mnemonic: "AND", opcode: 0b0101, discriminator: Some((5, 1)), fixed: vec![],
+            fields: vec![reg("dst", 11, 9), reg("src", 8, 6), offset("imm", 4, 0)] },
+        Variant { name: "LoadReg", mnemonic: "LDR", opcode: 0b0110, discriminator: None, fixed: vec![],
+            fields: vec![reg("dst", 11, 9), reg("base", 8, 6), offset("offset", 5, 0)] },
+        Variant { name: "StoreReg", mnemonic: "STR", opcode: 0b0111, discriminator: None, fixed: vec![],
+            fields: vec![reg("src", 11, 9), reg("base", 8, 6), offset("offset", 5, 0)] },
+        Variant { name: "Rti", mnemonic: "RTI", opcode: 0b1000, discriminator: None, fixed: vec![], fields: vec![] },
+        Variant { name: "Not", mnemonic: "NOT", opcode: 0b1001, discriminator: None, fixed: vec![fixed(5, 0, 0b111111)],
+            fields: vec![reg("dst", 11, 9), reg("src", 8, 6)] },
+        Variant { name: "LoadInd", mnemonic: "LDI", opcode: 0b1010, discriminator: None, fixed: vec![],
+            fields: vec![reg("dst", 11, 9), offset("offset", 8, 0)] },
+        Variant { name: "StoreInd", mnemonic: "STI", opcode: 0b1011, discriminator: None, fixed: vec![],
+            fields: vec![reg("src", 11, 9), offset("offset", 8, 0)] },
+        Variant { name: "Jump", mnemonic: "JMP", opcode: 0b1100, discriminator: None, fixed: vec![],
+            fields: vec![reg("base", 8, 6)] },
+        Variant { name: "Reserved", mnemonic: "RESERVED", opcode: 0b1101, discriminator: None, fixed: vec![], fields: vec![] },
+        Variant { name: "LoadEffAddr", mnemonic: "LEA", opcode: 0b1110, discriminator: None, fixed: vec![],
+            fields: vec![reg("dst", 11, 9), offset("offset", 8, 0)] },
+        Variant { name: "Trap", mnemonic: "TRAP", opcode: 0b1111, discriminator: None, fixed: vec![],
+            fields: vec![byte("vector", 7, 0)] },
+    ];
+}
+
+fn field_type(kind: FieldKind) -> &'static str {
+    return match kind {
+        FieldKind::Reg => "u16",
+        FieldKind::Offset => "i16",
+        FieldKind::Byte => "u8",
+        FieldKind::Flag(_) => "bool",
+    };
+}
+
+fn generate_enum(out: &mut String, instructions: &[Variant]) {
+    out.push_str("#[derive(Debug, PartialEq)]\npub enum Op {\n");
+    for variant in instructions {
+        if variant.fields.is_empty() {
+            out.push_str(&format!("    {},\n", variant.name));
+            continue;
+        }
+        out.push_str(&format!("    {} {{\n", variant.name));
+        for field in &variant.fields {
+            out.push_str(&format!("        {}: {},\n", field.name, field_type(field.kind)));
+        }
+        out.push_str("    },\n");
+    }
+    out.push_str("}\n\n");
+}
+
+fn generate_decode(out: &mut String, instructions: &[Variant]) {
+    out.push_str("pub fn decode(instr: u16) -> Option<Op> {\n");
+    out.push_str("    return match select_u16(instr, 15, 12) {\n");
+    for opcode in 0..16u16 {
+        let siblings: Vec<&Variant> = instructions.iter().filter(|v| v.opcode == opcode).collect();
+        if siblings.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("        {:#06b} => ", opcode));
+        if siblings.len() == 1 {
+            out.push_str(&decode_construction(siblings[0]));
+            out.push_str(",\n");
+        } else {
+            let bit = siblings[0].discriminator.unwrap().0;
+            out.push_str(&format!("match select_bool(instr, {}) {{\n", bit));
+            for sibling in &siblings {
+                let value = sibling.discriminator.unwrap().1 != 0;
+                out.push_str(&format!("            {} => {},\n", value, decode_construction(sibling)));
+            }
+            out.push_str("        },\n");
+        }
+    }
+    out.push_str("        // Every 4-bit pattern is handled above; this only exists because\n");
+    out.push_str("        // `select_u16` returns a `u16` the compiler can't bound to 4 bits.\n");
+    out.push_str("        _unreachable => None,\n");
+    out.push_str("    };\n}\n\n");
+}
+
+fn decode_construction(variant: &Variant) -> String {
+    if variant.fields.is_empty() {
+        return format!("Some(Op::{})", variant.name);
+    }
+    let mut body = format!("Some(Op::{} {{\n", variant.name);
+    for field in &variant.fields {
+        let expr = match field.kind {
+            FieldKind::Reg => format!("select_u16(instr, {}, {})", field.start, field.end),
+            FieldKind::Offset => format!("select_i16(instr, {}, {})", field.start, field.end),
+            FieldKind::Byte => format!("select_u16(instr, {}, {}) as u8", field.start, field.end),
+            FieldKind::Flag(_) => format!("select_bool(instr, {})", field.start),
+        };
+        body.push_str(&format!("            {}: {},\n", field.name, expr));
+    }
+    body.push_str("        })");
+    return body;
+}
+
+fn generate_encode(out: &mut String, instructions: &[Variant]) {
+    out.push_str("pub fn encode(op: &Op) -> u16 {\n");
+    out.push_str("    return match op {\n");
+    for variant in instructions {
+        let pattern = if variant.fields.is_empty() {
+            format!("Op::{}", variant.name)
+        } else {
+            let bindings: Vec<String> = variant.fields.iter().map(|f| f.name.to_string()).collect();
+            format!("Op::{} {{ {} }}", variant.name, bindings.join(", "))
+        };
+        out.push_str(&format!("        {} => {{\n", pattern));
+        out.push_str(&format!("            place_u16({:#06b}, 15, 12)\n", variant.opcode));
+        if let Some((bit, value)) = variant.discriminator {
+            out.push_str(&format!("                | place_u16({}, {}, {})\n", value, bit, bit));
+        }
+        for f in &variant.fixed {
+            out.push_str(&format!("                | place_u16({:#b}, {}, {})\n", f.value, f.start, f.end));
+        }
+        for field in &variant.fields {
+            let expr = match field.kind {
+                FieldKind::Reg => format!("place_u16(*{}, {}, {})", field.name, field.start, field.end),
+                FieldKind::Offset => format!("place_i16(*{}, {}, {})", field.name, field.start, field.end),
+                FieldKind::Byte => format!("place_u16(*{} as u16, {}, {})", field.name, field.start, field.end),
+                FieldKind::Flag(_) => format!("place_u16(*{} as u16, {}, {})", field.name, field.start, field.end),
+            };
+            out.push_str(&format!("                | {}\n", expr));
+        }
+        out.push_str("        }\n");
+    }
+    out.push_str("    };\n}\n\n");
+}
+
+fn generate_disassemble(out: &mut String, instructions: &[Variant]) {
+    out.push_str("// Renders an `Op` back to canonical LC-3 assembly text, e.g. `ADD R0, R1, #3`.\n");
+    out.push_str("pub fn disassemble(op: &Op) -> String {\n");
+    out.push_str("    return match op {\n");
+    for variant in instructions {
+        let pattern = if variant.fields.is_empty() {
+            format!("Op::{}", variant.name)
+        } else {
+            let bindings: Vec<String> = variant.fields.iter().map(|f| f.name.to_string()).collect();
+            format!("Op::{} {{ {} }}", variant.name, bindings.join(", "))
+        };
+        let flags: Vec<&Field> = variant.fields.iter().filter(|f| matches!(f.kind, FieldKind::Flag(_))).collect();
+        let operands: Vec<&Field> = variant.fields.iter().filter(|f| !matches!(f.kind, FieldKind::Flag(_))).collect();
+
+        let mut mnemonic_expr = format!("String::from(\"{}\")", variant.mnemonic);
+        for flag_field in &flags {
+            let letter = match flag_field.kind {
+                FieldKind::Flag(letter) => letter,
+                _ => unreachable!(),
+            };
+            mnemonic_expr = format!(
+                "{{ let mut m = {}; if *{} {{ m.push('{}'); }} m }}",
+                mnemonic_expr, flag_field.name, letter
+            );
+        }
+
+        let operand_exprs: Vec<String> = operands
+            .iter()
+            .map(|field| match field.kind {
+                FieldKind::Reg => format!("format!(\"R{{}}\", {})", field.name),
+                FieldKind::Offset => format!("format!(\"#{{}}\", {})", field.name),
+                FieldKind::Byte => format!("format!(\"x{{:02X}}\", {})", field.name),
+                FieldKind::Flag(_) => unreachable!(),
+            })
+            .collect();
+
+        out.push_str(&format!("        {} => {{\n", pattern));
+        out.push_str(&format!("            let mnemonic = {};\n", mnemonic_expr));
+        if operand_exprs.is_empty() {
+            out.push_str("            mnemonic\n");
+        } else {
+            out.push_str("            let operands: Vec<String> = vec![\n");
+            for expr in &operand_exprs {
+                out.push_str(&format!("                {},\n", expr));
+            }
+            out.push_str("            ];\n");
+            out.push_str("            format!(\"{} {}\", mnemonic, operands.join(\", \"))\n");
+        }
+        out.push_str("        }\n");
+    }
+    out.push_str("    };\n}\n");
+}
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let dest = Path::new(&out_dir).join("ops_generated.rs");
+
+    let instructions = instructions();
+    let mut out = String::new();
+    generate_enum(&mut out, &instructions);
+    generate_decode(&mut out, &instructions);
+    generate_encode(&mut out, &instructions);
+    generate_disassemble(&mut out, &instructions);
+
+    fs::write(&dest, out).expect("failed to write generated opcode tables");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,437 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ops::{encode, Op};
+
+#[derive(Debug)]
+pub enum AsmError {
+    NoOrigin,
+    UnknownMnemonic(String),
+    UnknownLabel(String),
+    BadRegister(String),
+    BadImmediate(String),
+    OffsetOutOfRange(String),
+    ImmediateOutOfRange(String),
+    MissingOperand(String),
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsmError::NoOrigin => write!(fmt, "program is missing a .ORIG directive"),
+            AsmError::UnknownMnemonic(mnemonic) => write!(fmt, "unknown mnemonic: {}", mnemonic),
+            AsmError::UnknownLabel(label) => write!(fmt, "unknown label: {}", label),
+            AsmError::BadRegister(token) => write!(fmt, "not a register: {}", token),
+            AsmError::BadImmediate(token) => write!(fmt, "not an immediate: {}", token),
+            AsmError::OffsetOutOfRange(token) => write!(fmt, "offset out of range: {}", token),
+            AsmError::ImmediateOutOfRange(token) => write!(fmt, "immediate out of range: {}", token),
+            AsmError::MissingOperand(mnemonic) => write!(fmt, "missing operand for {}", mnemonic),
+        }
+    }
+}
+
+// One piece of assembled output: either a real instruction or a literal word
+// produced by a .FILL/.STRINGZ/.BLKW directive.
+enum Item {
+    Instr(Op),
+    Word(u16),
+}
+
+struct Line {
+    label: Option<String>,
+    mnemonic: Option<String>,
+    operands: Vec<String>,
+}
+
+fn strip_comment(line: &str) -> &str {
+    return match line.find(';') {
+        Some(index) => &line[..index],
+        None => line,
+    };
+}
+
+fn tokenize(line: &str) -> Vec<String> {
+    return strip_comment(line)
+        .replace(',', " ")
+        .split_whitespace()
+        .map(|token| token.to_string())
+        .collect();
+}
+
+fn is_directive_or_mnemonic(token: &str) -> bool {
+    let upper = token.to_uppercase();
+    const KEYWORDS: &[&str] = &[
+        ".ORIG", ".FILL", ".STRINGZ", ".BLKW", ".END", "ADD", "AND", "NOT", "LD", "LDI", "LDR",
+        "LEA", "ST", "STI", "STR", "JSR", "JSRR", "JMP", "RET", "RTI", "TRAP", "HALT", "GETC",
+        "OUT", "PUTS", "IN", "PUTSP",
+    ];
+    if KEYWORDS.contains(&upper.as_str()) {
+        return true;
+    }
+    // BR, plus any subset of its N/Z/P condition-code suffix (BRnzp, BRz,
+    // BRnp, ...) — not a bare prefix match, so labels like BRANCH1 or BREAK
+    // aren't mistaken for a branch mnemonic.
+    return match upper.strip_prefix("BR") {
+        Some(flags) => flags.chars().all(|c| c == 'N' || c == 'Z' || c == 'P'),
+        None => false,
+    };
+}
+
+fn parse_line(raw: &str) -> Option<Line> {
+    let tokens = tokenize(raw);
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let mut tokens = tokens.into_iter().peekable();
+    let mut label = None;
+    if let Some(first) = tokens.peek() {
+        if !is_directive_or_mnemonic(first) {
+            label = Some(tokens.next().unwrap());
+        }
+    }
+
+    let mnemonic = tokens.next();
+    let operands: Vec<String> = tokens.collect();
+    return Some(Line {
+        label,
+        mnemonic,
+        operands,
+    });
+}
+
+fn parse_register(token: &str) -> Result<u16, AsmError> {
+    let upper = token.to_uppercase();
+    if upper.len() == 2 && upper.starts_with('R') {
+        if let Some(digit) = upper.chars().nth(1).unwrap().to_digit(10) {
+            if digit <= 7 {
+                return Ok(digit as u16);
+            }
+        }
+    }
+    return Err(AsmError::BadRegister(token.to_string()));
+}
+
+fn parse_immediate(token: &str) -> Result<i32, AsmError> {
+    let (negative, body) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token),
+    };
+    let value = if let Some(hex) = body.strip_prefix('x').or(body.strip_prefix('X')) {
+        i32::from_str_radix(hex, 16).map_err(|_| AsmError::BadImmediate(token.to_string()))?
+    } else if let Some(dec) = body.strip_prefix('#') {
+        dec.parse().map_err(|_| AsmError::BadImmediate(token.to_string()))?
+    } else {
+        body.parse().map_err(|_| AsmError::BadImmediate(token.to_string()))?
+    };
+    return Ok(if negative { -value } else { value });
+}
+
+fn fits_signed(value: i32, bits: u32) -> bool {
+    let min = -(1i32 << (bits - 1));
+    let max = (1i32 << (bits - 1)) - 1;
+    return value >= min && value <= max;
+}
+
+// Bounds-checked operand access: `mnemonic` is only used to name the error,
+// so a malformed operand count (`ADD R0, R1`) reports `AsmError` instead of
+// panicking on an out-of-range slice index.
+fn operand<'a>(operands: &'a [String], index: usize, mnemonic: &str) -> Result<&'a str, AsmError> {
+    return operands
+        .get(index)
+        .map(|token| token.as_str())
+        .ok_or_else(|| AsmError::MissingOperand(mnemonic.to_string()));
+}
+
+// Like `parse_immediate`, but for a fixed-width immediate field (as opposed
+// to a PC-relative offset, which goes through `pc_offset`'s own check).
+fn checked_immediate(token: &str, bits: u32) -> Result<i16, AsmError> {
+    let value = parse_immediate(token)?;
+    if !fits_signed(value, bits) {
+        return Err(AsmError::ImmediateOutOfRange(token.to_string()));
+    }
+    return Ok(value as i16);
+}
+
+// TRAP vectors are an unsigned 8-bit field, so they get their own range
+// check rather than going through `fits_signed`.
+fn checked_vector(token: &str) -> Result<u8, AsmError> {
+    let value = parse_immediate(token)?;
+    if value < 0 || value > 0xff {
+        return Err(AsmError::ImmediateOutOfRange(token.to_string()));
+    }
+    return Ok(value as u8);
+}
+
+fn pc_offset(
+    target: &str,
+    labels: &BTreeMap<String, u16>,
+    next_pc: u16,
+    bits: u32,
+) -> Result<i16, AsmError> {
+    let address = *labels
+        .get(target)
+        .ok_or_else(|| AsmError::UnknownLabel(target.to_string()))?;
+    let offset = (address as i32) - (next_pc as i32);
+    if !fits_signed(offset, bits) {
+        return Err(AsmError::OffsetOutOfRange(target.to_string()));
+    }
+    return Ok(offset as i16);
+}
+
+fn trap_vector(mnemonic: &str) -> Option<u8> {
+    return match mnemonic {
+        "HALT" => Some(0x25),
+        "GETC" => Some(0x20),
+        "OUT" => Some(0x21),
+        "PUTS" => Some(0x22),
+        "IN" => Some(0x23),
+        "PUTSP" => Some(0x24),
+        _ => None,
+    };
+}
+
+// Assembles LC-3 source text into the same big-endian word format that
+// `load_executable` reads: the first word is the origin, and every
+// subsequent word is one instruction or literal in program order.
+pub fn assemble(source: &str) -> Result<Vec<u16>, AsmError> {
+    let lines: Vec<Line> = source.lines().filter_map(parse_line).collect();
+
+    let mut origin: Option<u16> = None;
+    let mut labels: BTreeMap<String, u16> = BTreeMap::new();
+    let mut pc: u16 = 0;
+
+    // Pass 1: resolve .ORIG and assign every label an address.
+    for line in &lines {
+        let mnemonic = match &line.mnemonic {
+            Some(mnemonic) => mnemonic.to_uppercase(),
+            None => {
+                if let Some(label) = &line.label {
+                    labels.insert(label.clone(), pc);
+                }
+                continue;
+            }
+        };
+
+        if mnemonic == ".ORIG" {
+            let base = parse_immediate(operand(&line.operands, 0, &mnemonic)?)?;
+            origin = Some(base as u16);
+            pc = base as u16;
+            continue;
+        }
+        if mnemonic == ".END" {
+            continue;
+        }
+
+        if let Some(label) = &line.label {
+            labels.insert(label.clone(), pc);
+        }
+
+        pc = pc.wrapping_add(match mnemonic.as_str() {
+            ".FILL" => 1,
+            ".STRINGZ" => {
+                let text = line.operands.join(" ");
+                let text = text.trim_matches('"');
+                (text.len() + 1) as u16
+            }
+            ".BLKW" => parse_immediate(operand(&line.operands, 0, &mnemonic)?)? as u16,
+            _ => 1,
+        });
+    }
+
+    let origin = origin.ok_or(AsmError::NoOrigin)?;
+
+    // Pass 2: emit instructions/literals now that every label has an address.
+    let mut items: Vec<Item> = vec![];
+    let mut pc = origin;
+    for line in &lines {
+        let mnemonic = match &line.mnemonic {
+            Some(mnemonic) => mnemonic.to_uppercase(),
+            None => continue,
+        };
+        if mnemonic == ".ORIG" || mnemonic == ".END" {
+            continue;
+        }
+
+        let operands = &line.operands;
+        let next_pc = pc.wrapping_add(1);
+        let item = match mnemonic.as_str() {
+            ".FILL" => Item::Word(parse_immediate(operand(operands, 0, &mnemonic)?)? as u16),
+            ".BLKW" => {
+                let count = parse_immediate(operand(operands, 0, &mnemonic)?)? as u16;
+                for _ in 0..count {
+                    items.push(Item::Word(0));
+                }
+                pc = pc.wrapping_add(count);
+                continue;
+            }
+            ".STRINGZ" => {
+                let text = operands.join(" ");
+                let text = text.trim_matches('"');
+                for ch in text.chars() {
+                    items.push(Item::Word(ch as u16));
+                    pc = pc.wrapping_add(1);
+                }
+                items.push(Item::Word(0));
+                pc = pc.wrapping_add(1);
+                continue;
+            }
+            "ADD" => {
+                let dst = parse_register(operand(operands, 0, &mnemonic)?)?;
+                let src = parse_register(operand(operands, 1, &mnemonic)?)?;
+                let src2 = operand(operands, 2, &mnemonic)?;
+                if let Ok(src2) = parse_register(src2) {
+                    Item::Instr(Op::AddReg { dst, src1: src, src2 })
+                } else {
+                    let imm = checked_immediate(src2, 5)?;
+                    Item::Instr(Op::AddImm { dst, src, imm })
+                }
+            }
+            "AND" => {
+                let dst = parse_register(operand(operands, 0, &mnemonic)?)?;
+                let src = parse_register(operand(operands, 1, &mnemonic)?)?;
+                let src2 = operand(operands, 2, &mnemonic)?;
+                if let Ok(src2) = parse_register(src2) {
+                    Item::Instr(Op::AndReg { dst, src1: src, src2 })
+                } else {
+                    let imm = checked_immediate(src2, 5)?;
+                    Item::Instr(Op::AndImm { dst, src, imm })
+                }
+            }
+            "NOT" => Item::Instr(Op::Not {
+                dst: parse_register(operand(operands, 0, &mnemonic)?)?,
+                src: parse_register(operand(operands, 1, &mnemonic)?)?,
+            }),
+            "LD" => Item::Instr(Op::Load {
+                dst: parse_register(operand(operands, 0, &mnemonic)?)?,
+                offset: pc_offset(operand(operands, 1, &mnemonic)?, &labels, next_pc, 9)?,
+            }),
+            "LDI" => Item::Instr(Op::LoadInd {
+                dst: parse_register(operand(operands, 0, &mnemonic)?)?,
+                offset: pc_offset(operand(operands, 1, &mnemonic)?, &labels, next_pc, 9)?,
+            }),
+            "LDR" => Item::Instr(Op::LoadReg {
+                dst: parse_register(operand(operands, 0, &mnemonic)?)?,
+                base: parse_register(operand(operands, 1, &mnemonic)?)?,
+                offset: checked_immediate(operand(operands, 2, &mnemonic)?, 6)?,
+            }),
+            "LEA" => Item::Instr(Op::LoadEffAddr {
+                dst: parse_register(operand(operands, 0, &mnemonic)?)?,
+                offset: pc_offset(operand(operands, 1, &mnemonic)?, &labels, next_pc, 9)?,
+            }),
+            "ST" => Item::Instr(Op::Store {
+                src: parse_register(operand(operands, 0, &mnemonic)?)?,
+                offset: pc_offset(operand(operands, 1, &mnemonic)?, &labels, next_pc, 9)?,
+            }),
+            "STI" => Item::Instr(Op::StoreInd {
+                src: parse_register(operand(operands, 0, &mnemonic)?)?,
+                offset: pc_offset(operand(operands, 1, &mnemonic)?, &labels, next_pc, 9)?,
+            }),
+            "STR" => Item::Instr(Op::StoreReg {
+                src: parse_register(operand(operands, 0, &mnemonic)?)?,
+                base: parse_register(operand(operands, 1, &mnemonic)?)?,
+                offset: checked_immediate(operand(operands, 2, &mnemonic)?, 6)?,
+            }),
+            "JSR" => Item::Instr(Op::Call {
+                offset: pc_offset(operand(operands, 0, &mnemonic)?, &labels, next_pc, 11)?,
+            }),
+            "JSRR" => Item::Instr(Op::CallReg {
+                src: parse_register(operand(operands, 0, &mnemonic)?)?,
+            }),
+            "JMP" => Item::Instr(Op::Jump {
+                base: parse_register(operand(operands, 0, &mnemonic)?)?,
+            }),
+            "RET" => Item::Instr(Op::Jump { base: 7 }),
+            "RTI" => Item::Instr(Op::Rti),
+            "TRAP" => Item::Instr(Op::Trap {
+                vector: checked_vector(operand(operands, 0, &mnemonic)?)?,
+            }),
+            mnemonic if mnemonic.starts_with("BR") => {
+                let flags = &mnemonic[2..];
+                let (n, z, p) = if flags.is_empty() {
+                    (true, true, true)
+                } else {
+                    (flags.contains('N'), flags.contains('Z'), flags.contains('P'))
+                };
+                Item::Instr(Op::Branch {
+                    n,
+                    z,
+                    p,
+                    offset: pc_offset(operand(operands, 0, mnemonic)?, &labels, next_pc, 9)?,
+                })
+            }
+            mnemonic => match trap_vector(mnemonic) {
+                Some(vector) => Item::Instr(Op::Trap { vector }),
+                None => return Err(AsmError::UnknownMnemonic(mnemonic.to_string())),
+            },
+        };
+        items.push(item);
+        pc = next_pc;
+    }
+
+    let mut words = vec![origin];
+    for item in items {
+        words.push(match item {
+            Item::Instr(op) => encode(&op),
+            Item::Word(word) => word,
+        });
+    }
+    return Ok(words);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::decode;
+
+    #[test]
+    fn assembles_labels_and_directives() {
+        let source = "
+            .ORIG x3000
+            LEA R0, MSG
+            PUTS
+            HALT
+            MSG .STRINGZ \"hi\"
+            .END
+        ";
+        let words = assemble(source).unwrap();
+        assert_eq!(words[0], 0x3000);
+        assert_eq!(decode(words[1]), Some(Op::LoadEffAddr { dst: 0, offset: 2 }));
+        assert_eq!(decode(words[2]), Some(Op::Trap { vector: 0x22 }));
+        assert_eq!(decode(words[3]), Some(Op::Trap { vector: 0x25 }));
+        assert_eq!(words[4..7], [b'h' as u16, b'i' as u16, 0]);
+    }
+
+    #[test]
+    fn rejects_missing_origin() {
+        assert!(matches!(assemble("ADD R0, R0, R0"), Err(AsmError::NoOrigin)));
+    }
+
+    #[test]
+    fn labels_beginning_with_br_are_not_mistaken_for_branches() {
+        let source = ".ORIG x3000\nBRANCH1 ADD R0, R0, R1\nHALT\n.END";
+        let words = assemble(source).unwrap();
+        assert_eq!(decode(words[1]), Some(Op::AddReg { dst: 0, src1: 0, src2: 1 }));
+    }
+
+    #[test]
+    fn rejects_missing_operands_instead_of_panicking() {
+        let source = ".ORIG x3000\nADD R0, R1\nHALT\n.END";
+        assert!(matches!(assemble(source), Err(AsmError::MissingOperand(_))));
+    }
+
+    #[test]
+    fn rejects_immediate_out_of_range() {
+        let source = ".ORIG x3000\nADD R0, R1, #100\nHALT\n.END";
+        assert!(matches!(assemble(source), Err(AsmError::ImmediateOutOfRange(_))));
+    }
+
+    #[test]
+    fn rejects_trap_vector_out_of_range() {
+        let source = ".ORIG x3000\nTRAP x1FF\nHALT\n.END";
+        assert!(matches!(assemble(source), Err(AsmError::ImmediateOutOfRange(_))));
+    }
+}
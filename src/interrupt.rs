@@ -0,0 +1,159 @@
+use crate::memory::Memory;
+use crate::Io;
+
+// A device's request to interrupt the running program. The main loop only
+// honors it if `priority` exceeds the current PSR priority.
+pub struct Interrupt {
+    pub vector: u8,
+    pub priority: u8,
+}
+
+// A memory-mapped peripheral. Polled once per executed instruction by the
+// main loop's device registry, and consulted again on any load/store that
+// falls inside `handles`, so a device can both raise interrupts and own the
+// register values a program reads and writes.
+pub trait Device {
+    fn tick(&mut self, mem: &mut Memory, io: &mut dyn Io) -> Option<Interrupt>;
+
+    // Called only on the device whose interrupt from `tick` was actually
+    // delivered (i.e. `poll_devices` entered supervisor mode for it), so it
+    // can clear whatever armed the request. A device whose request re-arms
+    // through its own owning register (e.g. the keyboard's KBSR, cleared by
+    // reading KBDR) doesn't need to override this; one that disarms itself
+    // unconditionally in `tick` would drop its interrupt forever if it was
+    // masked by a higher current priority.
+    fn acknowledge(&mut self, mem: &mut Memory) {
+        let _ = mem;
+    }
+
+    // Whether this device owns the memory-mapped register at `address`.
+    // Loads/stores to addresses no device claims fall through to plain RAM.
+    fn handles(&self, address: u16) -> bool {
+        let _ = address;
+        return false;
+    }
+
+    // Services a data load from an address this device `handles`.
+    fn read(&mut self, mem: &mut Memory, io: &mut dyn Io, address: u16) -> u16 {
+        let _ = io;
+        return mem.peek(address);
+    }
+
+    // Services a data store to an address this device `handles`.
+    fn write(&mut self, mem: &mut Memory, io: &mut dyn Io, address: u16, value: u16) {
+        let _ = io;
+        mem.store(address, value);
+    }
+}
+
+const TMR: u16 = 0xfe08; // bit 15: enable, bits 10-8: priority
+const TMI: u16 = 0xfe0a; // countdown interval, reloaded by software
+const TMR_ENABLE: u16 = 1 << 15;
+
+pub const TIMER_VECTOR: u8 = 0x80;
+
+// Decrements TMI once per tick while TMR is enabled. On reaching zero it
+// requests an interrupt at TMR's priority on every subsequent tick until
+// that interrupt is actually delivered (see `acknowledge`) — a masked
+// request (current priority at or above TMR's) stays pending instead of
+// being dropped, and fires as soon as the priority is lowered enough.
+// Once delivered, software must rewrite TMI and re-set the enable bit to
+// restart it.
+pub struct TimerDevice;
+
+impl Device for TimerDevice {
+    fn tick(&mut self, mem: &mut Memory, _io: &mut dyn Io) -> Option<Interrupt> {
+        let ctrl = mem.peek(TMR);
+        if ctrl & TMR_ENABLE == 0 {
+            return None;
+        }
+
+        let remaining = mem.peek(TMI);
+        if remaining == 0 {
+            let priority = ((ctrl >> 8) & 0b111) as u8;
+            return Some(Interrupt {
+                vector: TIMER_VECTOR,
+                priority,
+            });
+        }
+
+        mem.store(TMI, remaining - 1);
+        return None;
+    }
+
+    fn acknowledge(&mut self, mem: &mut Memory) {
+        mem.store(TMR, mem.peek(TMR) & !TMR_ENABLE);
+    }
+}
+
+const KB_STATUS: u16 = 0xfe00; // KBSR
+const KB_DATA: u16 = 0xfe02; // KBDR
+const KBSR_READY: u16 = 1 << 15;
+const KBSR_IE: u16 = 1 << 14;
+
+pub const KEYBOARD_VECTOR: u8 = 0x81;
+pub const KEYBOARD_PRIORITY: u8 = 4;
+
+// Polls `Io::poll_key` (never blocking) once per tick. When a key is
+// waiting it latches KBSR's ready bit and the character into KBDR, mirroring
+// a real keyboard controller; reading KBDR clears the ready bit again so the
+// next poll can latch the following key.
+pub struct KeyboardDevice;
+
+impl Device for KeyboardDevice {
+    fn tick(&mut self, mem: &mut Memory, io: &mut dyn Io) -> Option<Interrupt> {
+        if mem.peek(KB_STATUS) & KBSR_READY == 0 {
+            if let Some(key) = io.poll_key() {
+                mem.store(KB_STATUS, mem.peek(KB_STATUS) | KBSR_READY);
+                mem.store(KB_DATA, key);
+            }
+        }
+
+        let status = mem.peek(KB_STATUS);
+        if status & KBSR_READY != 0 && status & KBSR_IE != 0 {
+            return Some(Interrupt {
+                vector: KEYBOARD_VECTOR,
+                priority: KEYBOARD_PRIORITY,
+            });
+        }
+        return None;
+    }
+
+    fn handles(&self, address: u16) -> bool {
+        return address == KB_STATUS || address == KB_DATA;
+    }
+
+    fn read(&mut self, mem: &mut Memory, _io: &mut dyn Io, address: u16) -> u16 {
+        let value = mem.peek(address);
+        if address == KB_DATA {
+            mem.store(KB_STATUS, mem.peek(KB_STATUS) & !KBSR_READY);
+        }
+        return value;
+    }
+}
+
+const DSR: u16 = 0xfe04;
+const DDR: u16 = 0xfe06;
+const DSR_READY: u16 = 1 << 15;
+
+// The console is always ready to accept another character, so DSR reads as
+// ready on every tick; a write to DDR prints immediately.
+pub struct DisplayDevice;
+
+impl Device for DisplayDevice {
+    fn tick(&mut self, mem: &mut Memory, _io: &mut dyn Io) -> Option<Interrupt> {
+        mem.store(DSR, DSR_READY);
+        return None;
+    }
+
+    fn handles(&self, address: u16) -> bool {
+        return address == DSR || address == DDR;
+    }
+
+    fn write(&mut self, mem: &mut Memory, io: &mut dyn Io, address: u16, value: u16) {
+        if address == DDR {
+            io.write_char(value as u8);
+        }
+        mem.store(address, value);
+    }
+}
@@ -0,0 +1,28 @@
+// The execution engine is no_std so it can be embedded without a console or
+// heap-backed OS; `cargo test` still links std so the modules' unit tests
+// can use the ordinary standard library.
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+pub mod asm;
+pub mod interrupt;
+mod memory;
+pub mod ops;
+mod registers;
+mod vm;
+
+pub use vm::{StepResult, Vm};
+
+// Everything the VM core needs from the outside world: a console to read
+// keystrokes from and echo characters to. Implement this for whatever
+// host you're embedding into (a terminal, a test harness, a UART) and the
+// core never has to know `std` exists.
+pub trait Io {
+    // Blocks until a character is available and returns it.
+    fn read_char(&mut self) -> u16;
+    // Writes one character to the console.
+    fn write_char(&mut self, c: u8);
+    // Returns a character if one is already waiting, without blocking.
+    fn poll_key(&mut self) -> Option<u16>;
+}
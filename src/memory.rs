@@ -0,0 +1,33 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[derive(Debug)]
+pub struct Memory {
+    memory: Vec<u16>,
+}
+
+impl Memory {
+    pub(crate) fn new() -> Memory {
+        return Memory {
+            memory: vec![0; 2usize.pow(16)],
+        };
+    }
+
+    // Plain, side-effect-free memory access. Memory-mapped registers are
+    // serviced by the device registry (see `Vm::mem_read`/`mem_write`), not
+    // here, so this is safe to use for instruction fetch, PC-relative
+    // addressing, and the supervisor stack alike.
+    pub(crate) fn peek(&self, address: u16) -> u16 {
+        return self.memory[address as usize];
+    }
+
+    pub(crate) fn store(&mut self, address: u16, value: u16) {
+        self.memory[address as usize] = value;
+    }
+
+    pub(crate) fn copy(&mut self, base: u16, block: &[u16]) {
+        for (offset, word) in block.iter().enumerate() {
+            self.store(base + offset as u16, *word);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::memory::Memory;
+
+// Initial supervisor and user stack pointers. R6 starts out as the user
+// stack; `enter_supervisor` swaps it onto the supervisor stack on entry
+// from user mode and `exit_supervisor` swaps it back, so a handler runs
+// on the supervisor stack without setting R6 itself.
+const SSP_INIT: u16 = 0x3000;
+const USP_INIT: u16 = 0xfe00;
+
+pub struct Registers {
+    r: Vec<u16>,
+    pub pc: u16,
+    pub n: bool,
+    pub z: bool,
+    pub p: bool,
+    // Processor Status Register fields not folded into n/z/p above.
+    pub privileged: bool,
+    pub priority: u8,
+    saved_ssp: u16,
+    saved_usp: u16,
+}
+
+impl Registers {
+    pub(crate) fn new() -> Registers {
+        let mut r = alloc::vec![0; 8];
+        r[6] = USP_INIT;
+        return Registers {
+            r,
+            pc: 0,
+            n: false,
+            z: false,
+            p: false,
+            privileged: false,
+            priority: 0,
+            saved_ssp: SSP_INIT,
+            saved_usp: USP_INIT,
+        };
+    }
+
+    pub fn get(&self, index: u16) -> u16 {
+        return self.r[index as usize];
+    }
+
+    pub fn set(&mut self, index: u16, value: u16) {
+        self.r[index as usize] = value;
+        if (value as i16) < 0 {
+            self.n = true;
+            self.z = false;
+            self.p = false;
+        } else if value == 0 {
+            self.n = false;
+            self.z = true;
+            self.p = false;
+        } else {
+            self.n = false;
+            self.z = false;
+            self.p = true;
+        }
+    }
+
+    // Like `set`, but for internal bookkeeping (stack pointer bumps, PSR/PC
+    // save-restore) that, unlike an ALU result, must not touch the condition codes.
+    fn set_quiet(&mut self, index: u16, value: u16) {
+        self.r[index as usize] = value;
+    }
+
+    // Packs the condition codes alongside privilege and priority into the
+    // 16-bit PSR word real LC-3 pushes to the supervisor stack on entry.
+    fn psr(&self) -> u16 {
+        return ((self.privileged as u16) << 15)
+            | ((self.priority as u16) << 8)
+            | ((self.n as u16) << 2)
+            | ((self.z as u16) << 1)
+            | (self.p as u16);
+    }
+
+    fn set_psr(&mut self, value: u16) {
+        self.privileged = value & (1 << 15) != 0;
+        self.priority = ((value >> 8) & 0b111) as u8;
+        self.n = value & (1 << 2) != 0;
+        self.z = value & (1 << 1) != 0;
+        self.p = value & 1 != 0;
+    }
+
+    // Enters supervisor mode to service a trap, exception, or device
+    // interrupt: swaps onto the supervisor stack, pushes the old PSR and
+    // PC, then jumps through `table_base[vector]`. `new_priority` raises
+    // the PSR priority for a device interrupt; traps and exceptions leave
+    // it as-is.
+    pub(crate) fn enter_supervisor(
+        &mut self,
+        mem: &mut Memory,
+        table_base: u16,
+        vector: u8,
+        new_priority: Option<u8>,
+    ) {
+        let psr = self.psr();
+        let pc = self.pc;
+        if !self.privileged {
+            self.saved_usp = self.get(6);
+            self.set_quiet(6, self.saved_ssp);
+        }
+        self.set_quiet(6, self.get(6).wrapping_sub(1));
+        mem.store(self.get(6), psr);
+        self.set_quiet(6, self.get(6).wrapping_sub(1));
+        mem.store(self.get(6), pc);
+        self.privileged = true;
+        if let Some(priority) = new_priority {
+            self.priority = priority;
+        }
+        self.pc = mem.peek(table_base.wrapping_add(vector as u16));
+    }
+
+    // The `RTI` side of `enter_supervisor`: pops PC then PSR, and drops
+    // back onto the user stack if the restored PSR is unprivileged.
+    pub(crate) fn exit_supervisor(&mut self, mem: &mut Memory) {
+        self.pc = mem.peek(self.get(6));
+        self.set_quiet(6, self.get(6).wrapping_add(1));
+        let psr = mem.peek(self.get(6));
+        self.set_quiet(6, self.get(6).wrapping_add(1));
+        self.set_psr(psr);
+        if !self.privileged {
+            self.saved_ssp = self.get(6);
+            self.set_quiet(6, self.saved_usp);
+        }
+    }
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &v) in self.r.iter().enumerate() {
+            fmt.write_fmt(format_args!("r{}=0x{:x} ", i, v))?;
+        }
+        fmt.write_fmt(format_args!("pc=0x{:x} ", self.pc))?;
+        fmt.write_fmt(format_args!("n={} ", self.n))?;
+        fmt.write_fmt(format_args!("z={} ", self.z))?;
+        fmt.write_fmt(format_args!("p={} ", self.p))?;
+        fmt.write_fmt(format_args!(
+            "priv={} priority={}",
+            self.privileged, self.priority
+        ))?;
+        Ok(())
+    }
+}
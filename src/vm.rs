@@ -0,0 +1,404 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::interrupt::{Device, DisplayDevice, Interrupt, KeyboardDevice, TimerDevice};
+use crate::memory::Memory;
+use crate::ops::{self, Op};
+use crate::registers::Registers;
+use crate::Io;
+
+const TRAP_VECTOR_TABLE: u16 = 0x0000;
+const INTERRUPT_VECTOR_TABLE: u16 = 0x0100;
+
+#[derive(Debug, PartialEq)]
+pub enum StepResult {
+    Continue,
+    Halted,
+}
+
+// The execution engine, extracted from `main` so it can be driven
+// programmatically (tests, embedders) instead of only by the CLI's fetch
+// loop. All terminal/device I/O goes through `io`, so the engine itself
+// never touches a concrete console.
+pub struct Vm {
+    pub regs: Registers,
+    pub mem: Memory,
+    pub io: Box<dyn Io>,
+    devices: Vec<Box<dyn Device>>,
+}
+
+impl Vm {
+    pub fn new(io: Box<dyn Io>) -> Vm {
+        return Vm {
+            regs: Registers::new(),
+            mem: Memory::new(),
+            io,
+            devices: vec![
+                Box::new(TimerDevice),
+                Box::new(KeyboardDevice),
+                Box::new(DisplayDevice),
+            ],
+        };
+    }
+
+    pub fn load(&mut self, base: u16, code: &[u16]) {
+        self.mem.copy(base, code);
+        self.regs.pc = base;
+    }
+
+    // Executes one instruction plus the device poll that follows it.
+    // Returns `StepResult::Halted` once the program hits `TRAP x25`.
+    pub fn step(&mut self) -> StepResult {
+        let instr = self.mem.peek(self.regs.pc);
+        self.regs.pc = self.regs.pc.wrapping_add(1);
+        let decoded = ops::decode(instr).unwrap();
+
+        let result = self.execute(decoded);
+        self.poll_devices();
+        return result;
+    }
+
+    pub fn run(&mut self) {
+        loop {
+            if self.step() == StepResult::Halted {
+                break;
+            }
+        }
+    }
+
+    // Reads `address`, routing through whichever device `handles` it (so
+    // KBSR/KBDR/DSR/DDR behave like real hardware registers) and falling
+    // through to plain RAM otherwise.
+    fn mem_read(&mut self, address: u16) -> u16 {
+        for device in self.devices.iter_mut() {
+            if device.handles(address) {
+                return device.read(&mut self.mem, self.io.as_mut(), address);
+            }
+        }
+        return self.mem.peek(address);
+    }
+
+    // The store counterpart of `mem_read`.
+    fn mem_write(&mut self, address: u16, value: u16) {
+        for device in self.devices.iter_mut() {
+            if device.handles(address) {
+                device.write(&mut self.mem, self.io.as_mut(), address, value);
+                return;
+            }
+        }
+        self.mem.store(address, value);
+    }
+
+    fn execute(&mut self, decoded: Op) -> StepResult {
+        match decoded {
+            Op::Rti => self.regs.exit_supervisor(&mut self.mem),
+            Op::Reserved => self.regs.enter_supervisor(&mut self.mem, TRAP_VECTOR_TABLE, 0x00, None),
+            Op::Not { dst, src } => self.regs.set(dst, !self.regs.get(src)),
+            Op::AddReg { dst, src1, src2 } => {
+                self.regs.set(dst, self.regs.get(src1).wrapping_add(self.regs.get(src2)));
+            }
+            Op::AddImm { dst, src, imm } => {
+                self.regs.set(dst, self.regs.get(src).wrapping_add(imm as u16));
+            }
+            Op::AndReg { dst, src1, src2 } => {
+                self.regs.set(dst, self.regs.get(src1) & self.regs.get(src2));
+            }
+            Op::AndImm { dst, src, imm } => {
+                self.regs.set(dst, self.regs.get(src) & imm as u16);
+            }
+            Op::Load { dst, offset } => {
+                let addr = self.regs.pc.wrapping_add(offset as u16);
+                let value = self.mem_read(addr);
+                self.regs.set(dst, value);
+            }
+            Op::LoadInd { dst, offset } => {
+                let addr = self.mem.peek(self.regs.pc.wrapping_add(offset as u16));
+                let value = self.mem_read(addr);
+                self.regs.set(dst, value);
+            }
+            Op::LoadReg { dst, base, offset } => {
+                let addr = self.regs.get(base).wrapping_add(offset as u16);
+                let value = self.mem_read(addr);
+                self.regs.set(dst, value);
+            }
+            Op::LoadEffAddr { dst, offset } => {
+                let addr = self.regs.pc.wrapping_add(offset as u16);
+                self.regs.set(dst, addr);
+            }
+            Op::Store { src, offset } => {
+                let addr = self.regs.pc.wrapping_add(offset as u16);
+                self.mem_write(addr, self.regs.get(src));
+            }
+            Op::StoreInd { src, offset } => {
+                let addr = self.mem.peek(self.regs.pc.wrapping_add(offset as u16));
+                self.mem_write(addr, self.regs.get(src));
+            }
+            Op::StoreReg { src, base, offset } => {
+                let addr = self.regs.get(base).wrapping_add(offset as u16);
+                self.mem_write(addr, self.regs.get(src));
+            }
+            Op::Call { offset } => {
+                self.regs.set(7, self.regs.pc);
+                self.regs.pc = self.regs.pc.wrapping_add(offset as u16);
+            }
+            Op::CallReg { src } => {
+                self.regs.pc = self.regs.get(src);
+            }
+            Op::Branch { n, z, p, offset } => {
+                if n && self.regs.n || z && self.regs.z || p && self.regs.p {
+                    self.regs.pc = self.regs.pc.wrapping_add(offset as u16);
+                }
+            }
+            Op::Jump { base } => {
+                self.regs.pc = self.regs.get(base);
+            }
+            Op::Trap { vector } => match vector {
+                0x20 => {
+                    // getc
+                    let c = self.io.read_char();
+                    self.regs.set(0, c);
+                }
+                0x21 => {
+                    // putc
+                    self.io.write_char(self.regs.get(0) as u8);
+                }
+                0x22 => {
+                    // puts
+                    let mut i = self.regs.get(0);
+                    loop {
+                        let c = self.mem_read(i);
+                        match c {
+                            0 => break,
+                            c => self.io.write_char((c & 0xff) as u8),
+                        }
+                        i += 1;
+                    }
+                }
+                0x23 => {
+                    // in: prompt, read and echo a character into r0
+                    for c in "Input a character> ".bytes() {
+                        self.io.write_char(c);
+                    }
+                    let c = self.io.read_char();
+                    self.io.write_char(c as u8);
+                    self.regs.set(0, c);
+                }
+                0x24 => {
+                    // putsp: packed string, two characters per word, low byte first
+                    let mut i = self.regs.get(0);
+                    'outer: loop {
+                        let word = self.mem_read(i);
+                        for shift in [0, 8] {
+                            let c = ((word >> shift) & 0xff) as u8;
+                            if c == 0 {
+                                break 'outer;
+                            }
+                            self.io.write_char(c);
+                        }
+                        i += 1;
+                    }
+                }
+                0x25 => {
+                    return StepResult::Halted;
+                }
+                vector => self.regs.enter_supervisor(&mut self.mem, TRAP_VECTOR_TABLE, vector, None),
+            },
+        }
+        return StepResult::Continue;
+    }
+
+    fn poll_devices(&mut self) {
+        let mut pending: Option<(usize, Interrupt)> = None;
+        for (index, device) in self.devices.iter_mut().enumerate() {
+            if let Some(interrupt) = device.tick(&mut self.mem, self.io.as_mut()) {
+                let supersedes = match &pending {
+                    Some((_, current)) => interrupt.priority > current.priority,
+                    None => true,
+                };
+                if supersedes {
+                    pending = Some((index, interrupt));
+                }
+            }
+        }
+        if let Some((index, interrupt)) = pending {
+            if interrupt.priority > self.regs.priority {
+                self.regs.enter_supervisor(
+                    &mut self.mem,
+                    INTERRUPT_VECTOR_TABLE,
+                    interrupt.vector,
+                    Some(interrupt.priority),
+                );
+                self.devices[index].acknowledge(&mut self.mem);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::interrupt::TIMER_VECTOR;
+    use crate::ops::encode;
+
+    const TMR: u16 = 0xfe08;
+    const TMR_ENABLE: u16 = 1 << 15;
+
+    // A scripted `Io`: feeds queued input one character at a time and
+    // records everything written to a shared buffer the test can inspect
+    // afterward, so VM behavior can be asserted on deterministically
+    // instead of driving a real terminal.
+    struct BufferIo {
+        input: VecDeque<u16>,
+        output: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Io for BufferIo {
+        fn read_char(&mut self) -> u16 {
+            return self.input.pop_front().unwrap_or(0);
+        }
+
+        fn write_char(&mut self, c: u8) {
+            self.output.borrow_mut().push(c);
+        }
+
+        fn poll_key(&mut self) -> Option<u16> {
+            return self.input.pop_front();
+        }
+    }
+
+    #[test]
+    fn runs_until_halt_and_echoes_output() {
+        let output = Rc::new(RefCell::new(vec![]));
+        let mut vm = Vm::new(Box::new(BufferIo {
+            input: VecDeque::new(),
+            output: output.clone(),
+        }));
+        let program = [
+            encode(&Op::AddImm { dst: 0, src: 0, imm: 15 }), // imm is a 5-bit field
+            encode(&Op::Trap { vector: 0x21 }), // putc
+            encode(&Op::Trap { vector: 0x25 }), // halt
+        ];
+        vm.load(0x3000, &program);
+        vm.run();
+
+        assert_eq!(*output.borrow(), vec![15]);
+    }
+
+    #[test]
+    fn keyboard_device_latches_polled_key_into_kbdr() {
+        let output = Rc::new(RefCell::new(vec![]));
+        let mut vm = Vm::new(Box::new(BufferIo {
+            input: VecDeque::from(vec![b'A' as u16]),
+            output,
+        }));
+        // Busy-wait on KBSR, then load KBDR into R0 and halt.
+        let program = [
+            encode(&Op::LoadInd { dst: 1, offset: 3 }), // R1 = KBSR
+            encode(&Op::Branch { n: false, z: true, p: false, offset: -2 }), // loop while KBSR == 0
+            encode(&Op::LoadInd { dst: 0, offset: 2 }), // R0 = KBDR
+            encode(&Op::Trap { vector: 0x25 }), // halt
+            0xfe00, // pointer to KBSR
+            0xfe02, // pointer to KBDR
+        ];
+        vm.load(0x3000, &program);
+        vm.run();
+
+        assert_eq!(vm.regs.get(0), b'A' as u16);
+    }
+
+    #[test]
+    fn timer_interrupt_saves_psr_pc_and_resumes_via_rti() {
+        let output = Rc::new(RefCell::new(vec![]));
+        let mut vm = Vm::new(Box::new(BufferIo { input: VecDeque::new(), output }));
+
+        // The interrupt service routine: prove it ran, then return.
+        let isr = [
+            encode(&Op::AddImm { dst: 2, src: 2, imm: 1 }),
+            encode(&Op::Rti),
+        ];
+        vm.mem.copy(0x0200, &isr);
+        vm.mem.store(INTERRUPT_VECTOR_TABLE + TIMER_VECTOR as u16, 0x0200);
+
+        // TMI is already zero, so enabling the timer fires it on the very
+        // next device poll. Priority 1 so it exceeds the VM's default
+        // (zero) priority and is actually delivered.
+        vm.mem.store(TMR, TMR_ENABLE | (1 << 8));
+        let program = [
+            encode(&Op::AddImm { dst: 1, src: 1, imm: 1 }), // the poll after this fires the interrupt
+            encode(&Op::AddImm { dst: 3, src: 3, imm: 7 }), // must run only once RTI resumes here
+            encode(&Op::Trap { vector: 0x25 }),
+        ];
+        vm.load(0x3000, &program);
+        vm.run();
+
+        assert_eq!(vm.regs.get(1), 1);
+        assert_eq!(vm.regs.get(2), 1); // the ISR ran
+        assert_eq!(vm.regs.get(3), 7); // and execution resumed exactly where it left off
+        assert!(!vm.regs.privileged); // RTI dropped us back out of supervisor mode
+    }
+
+    #[test]
+    fn lower_priority_interrupt_is_held_off_while_priority_is_raised() {
+        let output = Rc::new(RefCell::new(vec![]));
+        let mut vm = Vm::new(Box::new(BufferIo { input: VecDeque::new(), output }));
+
+        const TIMER_PRIORITY: u16 = 3;
+        vm.regs.priority = 5; // above the timer's priority below
+        vm.mem.store(TMR, TMR_ENABLE | (TIMER_PRIORITY << 8));
+
+        // If the interrupt (wrongly) fired, this would halt the program early.
+        vm.mem.copy(0x0200, &[encode(&Op::Trap { vector: 0x25 })]);
+        vm.mem.store(INTERRUPT_VECTOR_TABLE + TIMER_VECTOR as u16, 0x0200);
+
+        let program = [
+            encode(&Op::AddImm { dst: 1, src: 1, imm: 1 }), // the poll after this would fire the timer
+            encode(&Op::Trap { vector: 0x25 }),
+        ];
+        vm.load(0x3000, &program);
+        vm.run();
+
+        assert_eq!(vm.regs.pc, 0x3002); // halted via the program's own trap, never diverted
+        assert!(!vm.regs.privileged); // the masked interrupt never entered supervisor mode
+    }
+
+    #[test]
+    fn masked_timer_interrupt_stays_pending_until_priority_is_lowered() {
+        let output = Rc::new(RefCell::new(vec![]));
+        let mut vm = Vm::new(Box::new(BufferIo { input: VecDeque::new(), output }));
+
+        const TIMER_PRIORITY: u16 = 3;
+        vm.regs.priority = 5; // above the timer's priority, so it starts masked
+        vm.mem.store(TMR, TMR_ENABLE | (TIMER_PRIORITY << 8));
+
+        let isr = [
+            encode(&Op::AddImm { dst: 2, src: 2, imm: 1 }),
+            encode(&Op::Rti),
+        ];
+        vm.mem.copy(0x0200, &isr);
+        vm.mem.store(INTERRUPT_VECTOR_TABLE + TIMER_VECTOR as u16, 0x0200);
+
+        let program = [
+            encode(&Op::AddImm { dst: 1, src: 1, imm: 1 }), // poll after this is masked
+            encode(&Op::AddImm { dst: 1, src: 1, imm: 1 }), // poll after this delivers it
+            encode(&Op::Trap { vector: 0x25 }),
+        ];
+        vm.load(0x3000, &program);
+
+        vm.step(); // executes instr 0 and polls: masked, must not self-disarm
+        assert!(vm.mem.peek(TMR) & TMR_ENABLE != 0, "masked timer interrupt must stay pending");
+        assert_eq!(vm.regs.get(2), 0); // the ISR has not run yet
+
+        vm.regs.priority = 0; // unmask; the still-pending request should now fire
+        vm.run();
+
+        assert_eq!(vm.regs.get(1), 2); // both AddImm instructions in the program ran
+        assert_eq!(vm.regs.get(2), 1); // the ISR ran exactly once
+        assert_eq!(vm.mem.peek(TMR) & TMR_ENABLE, 0); // disarmed only once actually delivered
+        assert!(!vm.regs.privileged);
+    }
+}